@@ -1,19 +1,82 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use chrono::Local;
+use chrono::{DateTime, Local};
+use serde::Serialize;
 use tauri::AppHandle;
 
-use crate::config::{backup_dir_path, data_file_path, load_config};
+use crate::config::{backup_dir_path, data_file_path, load_config, BackupMode};
 
 const DATA_FILE: &str = "cmd_notebook.json";
+const ZSTD_EXT: &str = ".zst";
+const ZSTD_LEVEL: i32 = 3;
 
-/// 生成备份文件名，格式：cmd_notebook_YYYYMMDD_HHMMSS.json
+/// 生成一次性备份文件名，格式：cmd_notebook_YYYYMMDD_HHMMSS.json
 fn generate_backup_filename() -> String {
     let now = Local::now();
     format!("cmd_notebook_{}.json", now.format("%Y%m%d_%H%M%S"))
 }
 
+/// 去掉可能存在的 `.zst` 压缩后缀，便于按原始命名规则解析
+fn strip_zstd_ext(name: &str) -> &str {
+    name.strip_suffix(ZSTD_EXT).unwrap_or(name)
+}
+
+/// 扫描备份目录，找出当前最大的编号备份序号（不存在则为 0）
+fn max_numbered_index(backup_dir: &Path) -> u32 {
+    let prefix = format!("{}.~", DATA_FILE);
+    fs::read_dir(backup_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+                .filter_map(|name| {
+                    strip_zstd_ext(&name)
+                        .strip_prefix(prefix.as_str())
+                        .and_then(|rest| rest.strip_suffix('~'))
+                        .and_then(|index| index.parse::<u32>().ok())
+                })
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(0)
+}
+
+/// 按 `backup_mode` 计算本次应写入的备份文件路径（`None` 表示不备份），
+/// 开启压缩时追加 `.zst` 后缀
+fn backup_path_for_mode(backup_dir: &Path, mode: BackupMode, compress: bool) -> Option<PathBuf> {
+    let suffix = if compress { ZSTD_EXT } else { "" };
+    match mode {
+        BackupMode::None => None,
+        BackupMode::Simple => Some(backup_dir.join(format!("{}~{}", DATA_FILE, suffix))),
+        BackupMode::Numbered => {
+            let next_index = max_numbered_index(backup_dir) + 1;
+            Some(backup_dir.join(format!("{}.~{}~{}", DATA_FILE, next_index, suffix)))
+        }
+        BackupMode::Existing => {
+            if max_numbered_index(backup_dir) > 0 {
+                backup_path_for_mode(backup_dir, BackupMode::Numbered, compress)
+            } else {
+                backup_path_for_mode(backup_dir, BackupMode::Simple, compress)
+            }
+        }
+    }
+}
+
+/// 将数据文件写入备份路径，按需用 zstd 压缩
+fn write_backup(source: &Path, dest: &Path, compress: bool) -> Result<(), String> {
+    if compress {
+        let data = fs::read(source).map_err(|_| "读取数据文件失败".to_string())?;
+        let compressed =
+            zstd::encode_all(data.as_slice(), ZSTD_LEVEL).map_err(|_| "压缩备份失败".to_string())?;
+        fs::write(dest, compressed).map_err(|_| "写入备份失败".to_string())
+    } else {
+        fs::copy(source, dest)
+            .map(|_| ())
+            .map_err(|_| "创建备份失败".to_string())
+    }
+}
+
 /// 创建备份（仅在内容变化时）
 pub fn create_backup_if_changed(app: &AppHandle, new_content: &str) -> Result<(), String> {
     let data_path = data_file_path(app)?;
@@ -31,33 +94,74 @@ pub fn create_backup_if_changed(app: &AppHandle, new_content: &str) -> Result<()
         return Ok(());
     }
 
-    // 创建备份
+    let config = load_config(app)?;
     let backup_dir = backup_dir_path(app)?;
     fs::create_dir_all(&backup_dir).map_err(|_| "无法创建备份目录".to_string())?;
 
-    let backup_filename = generate_backup_filename();
-    let backup_path = backup_dir.join(&backup_filename);
+    let Some(backup_path) =
+        backup_path_for_mode(&backup_dir, config.backup_mode, config.compress_backups)
+    else {
+        return Ok(());
+    };
 
-    fs::copy(&data_path, &backup_path).map_err(|_| "创建备份失败".to_string())?;
+    write_backup(&data_path, &backup_path, config.compress_backups)?;
 
     // 清理过期备份
-    let config = load_config(app)?;
-    cleanup_old_backups(&backup_dir, config.backup_count)?;
+    cleanup_old_backups(&backup_dir, config.backup_count, config.backup_mode)?;
 
     Ok(())
 }
 
 /// 清理过期备份，保留最近 N 份
-fn cleanup_old_backups(backup_dir: &PathBuf, keep_count: usize) -> Result<(), String> {
+fn cleanup_old_backups(backup_dir: &Path, keep_count: usize, mode: BackupMode) -> Result<(), String> {
+    match mode {
+        BackupMode::Numbered | BackupMode::Existing => {
+            cleanup_numbered_backups(backup_dir, keep_count)?;
+            // 时间戳命名的备份（升级前遗留的，以及恢复、切换目录时 backup_file 生成的）不带编号，需单独清理，避免无限堆积
+            cleanup_timestamped_backups(backup_dir, keep_count)
+        }
+        BackupMode::Simple | BackupMode::None => cleanup_timestamped_backups(backup_dir, keep_count),
+    }
+}
+
+/// 按编号从大到小排序，只保留编号最大的 N 份
+fn cleanup_numbered_backups(backup_dir: &Path, keep_count: usize) -> Result<(), String> {
+    let prefix = format!("{}.~", DATA_FILE);
+    let mut backups: Vec<(u32, PathBuf)> = fs::read_dir(backup_dir)
+        .map_err(|_| "读取备份目录失败".to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            strip_zstd_ext(&name)
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_suffix('~'))
+                .and_then(|index| index.parse::<u32>().ok())
+                .map(|index| (index, entry.path()))
+        })
+        .collect();
+
+    if backups.len() <= keep_count {
+        return Ok(());
+    }
+
+    // 编号从大到小排序（最新的在前）
+    backups.sort_by(|a, b| b.0.cmp(&a.0));
+
+    for (_, path) in backups.into_iter().skip(keep_count) {
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(())
+}
+
+/// 按修改时间排序，只保留最近的 N 份（时间戳命名的备份）
+fn cleanup_timestamped_backups(backup_dir: &Path, keep_count: usize) -> Result<(), String> {
     let mut backups: Vec<_> = fs::read_dir(backup_dir)
         .map_err(|_| "读取备份目录失败".to_string())?
         .filter_map(|entry| entry.ok())
         .filter(|entry| {
-            entry
-                .file_name()
-                .to_string_lossy()
-                .starts_with("cmd_notebook_")
-                && entry.file_name().to_string_lossy().ends_with(".json")
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with("cmd_notebook_") && strip_zstd_ext(&name).ends_with(".json")
         })
         .collect();
 
@@ -111,3 +215,83 @@ pub fn copy_data_to_new_dir(app: &AppHandle, new_dir: &PathBuf) -> Result<(), St
 
     Ok(())
 }
+
+/// 单份备份的信息（返回给前端用于展示恢复列表）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub filename: String,
+    pub size: u64,
+    pub modified: String,
+}
+
+/// 列出备份目录下的所有备份，按修改时间从新到旧排序
+pub fn list_backup_files(app: &AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let backup_dir = backup_dir_path(app)?;
+
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups: Vec<_> = fs::read_dir(&backup_dir)
+        .map_err(|_| "读取备份目录失败".to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .collect();
+
+    backups.sort_by(|a, b| {
+        let time_a = a.metadata().and_then(|m| m.modified()).ok();
+        let time_b = b.metadata().and_then(|m| m.modified()).ok();
+        time_b.cmp(&time_a)
+    });
+
+    Ok(backups
+        .into_iter()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified: DateTime<Local> = metadata.modified().ok()?.into();
+            Some(BackupInfo {
+                filename: entry.file_name().to_string_lossy().to_string(),
+                size: metadata.len(),
+                modified: modified.format("%Y-%m-%d %H:%M:%S").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// 将指定备份恢复为当前数据文件，恢复前先备份当前数据以便可逆
+pub fn restore_backup_file(app: &AppHandle, filename: &str) -> Result<(), String> {
+    let backup_dir = backup_dir_path(app)?;
+    let backup_path = backup_dir.join(filename);
+
+    if !backup_path.exists() {
+        return Err("备份文件不存在".to_string());
+    }
+
+    let data_path = data_file_path(app)?;
+
+    // 恢复前备份当前数据，使本次操作可逆
+    if data_path.exists() {
+        backup_file(&data_path, &backup_dir)?;
+    }
+
+    let restored_content = if filename.ends_with(ZSTD_EXT) {
+        let compressed = fs::read(&backup_path).map_err(|_| "读取备份文件失败".to_string())?;
+        let decompressed =
+            zstd::decode_all(compressed.as_slice()).map_err(|_| "解压备份失败".to_string())?;
+        String::from_utf8(decompressed).map_err(|_| "备份内容不是有效的 UTF-8".to_string())?
+    } else {
+        fs::read_to_string(&backup_path).map_err(|_| "读取备份文件失败".to_string())?
+    };
+
+    if let Some(parent) = data_path.parent() {
+        fs::create_dir_all(parent).map_err(|_| "无法创建数据目录".to_string())?;
+    }
+
+    // 原子写入：先写临时文件，再 rename
+    let tmp_path = data_path.with_extension("json.tmp");
+    fs::write(&tmp_path, &restored_content).map_err(|_| "写入数据失败".to_string())?;
+    fs::rename(&tmp_path, &data_path).map_err(|_| "恢复数据失败".to_string())?;
+
+    Ok(())
+}
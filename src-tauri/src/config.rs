@@ -3,10 +3,32 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
 
+use crate::fsprobe::probe;
+
 const CONFIG_FILE: &str = "app_config.json";
 const DATA_FILE: &str = "cmd_notebook.json";
 const BACKUP_DIR: &str = ".backup";
 const DEFAULT_BACKUP_COUNT: usize = 10;
+const DEFAULT_LOCK_STALE_SECS: u64 = 600;
+const DEFAULT_GIT_HISTORY_BRANCH: &str = "main";
+
+/// 备份命名模式，仿照 GNU cp/install 的 `--backup` 语义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupMode {
+    /// 从不创建备份
+    None,
+    /// 始终覆盖同一个 `cmd_notebook.json~`
+    Simple,
+    /// 始终创建新的编号备份 `cmd_notebook.json.~N~`
+    Numbered,
+    /// 已存在编号备份则继续编号，否则退化为 Simple
+    Existing,
+}
+
+fn default_backup_mode() -> BackupMode {
+    BackupMode::Numbered
+}
 
 /// 应用配置（存储在系统 ConfigDir）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,19 +36,47 @@ pub struct AppConfig {
     /// 数据目录路径（用户可自定义）
     pub data_dir: PathBuf,
     /// 备份保留数量
-    #[serde(default = "default_backup_count")]  
+    #[serde(default = "default_backup_count")]
     pub backup_count: usize,
+    /// 备份命名模式
+    #[serde(default = "default_backup_mode")]
+    pub backup_mode: BackupMode,
+    /// 是否使用 zstd 压缩备份
+    #[serde(default)]
+    pub compress_backups: bool,
+    /// 目录锁陈旧判定超时（秒），超过此时长未更新的锁会被回收
+    #[serde(default = "default_lock_stale_secs")]
+    pub lock_stale_secs: u64,
+    /// 是否启用 Git 版本历史
+    #[serde(default)]
+    pub git_history_enabled: bool,
+    /// Git 版本历史使用的分支名
+    #[serde(default = "default_git_history_branch")]
+    pub git_history_branch: String,
 }
 
 fn default_backup_count() -> usize {
     DEFAULT_BACKUP_COUNT
 }
 
+fn default_lock_stale_secs() -> u64 {
+    DEFAULT_LOCK_STALE_SECS
+}
+
+fn default_git_history_branch() -> String {
+    DEFAULT_GIT_HISTORY_BRANCH.to_string()
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             data_dir: PathBuf::new(),
             backup_count: default_backup_count(),
+            backup_mode: default_backup_mode(),
+            compress_backups: false,
+            lock_stale_secs: default_lock_stale_secs(),
+            git_history_enabled: false,
+            git_history_branch: default_git_history_branch(),
         }
     }
 }
@@ -39,16 +89,21 @@ pub struct DataDirInfo {
     pub is_default: bool,
     pub data_file_exists: bool,
     pub is_writable: bool,
+    pub is_network: bool,
+    pub network_reason: Option<String>,
 }
 
 /// 切换目录时的检测结果
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum SwitchDirCheck {
-    EmptyDir,
+    EmptyDir {
+        is_network: bool,
+    },
     HasExistingData {
         #[serde(rename = "lastModified")]
         last_modified: String,
+        is_network: bool,
     },
     Invalid {
         reason: String,
@@ -109,6 +164,11 @@ pub fn load_config(app: &AppHandle) -> Result<AppConfig, String> {
         let config = AppConfig {
             data_dir: default_data_dir(app)?,
             backup_count: DEFAULT_BACKUP_COUNT,
+            backup_mode: default_backup_mode(),
+            compress_backups: false,
+            lock_stale_secs: default_lock_stale_secs(),
+            git_history_enabled: false,
+            git_history_branch: default_git_history_branch(),
         };
         eprintln!("[config] creating new config with data_dir: {:?}", config.data_dir);
         save_config(app, &config)?;
@@ -189,6 +249,8 @@ pub fn check_target_dir(path: &PathBuf) -> SwitchDirCheck {
         };
     }
 
+    let is_network = probe(path).is_network;
+
     let data_file = path.join(DATA_FILE);
     if data_file.exists() {
         let last_modified = match fs::metadata(&data_file) {
@@ -201,8 +263,11 @@ pub fn check_target_dir(path: &PathBuf) -> SwitchDirCheck {
             },
             Err(_) => "未知".to_string(),
         };
-        SwitchDirCheck::HasExistingData { last_modified }
+        SwitchDirCheck::HasExistingData {
+            last_modified,
+            is_network,
+        }
     } else {
-        SwitchDirCheck::EmptyDir
+        SwitchDirCheck::EmptyDir { is_network }
     }
 }
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+/// 目标路径所在文件系统的探测结果
+#[derive(Debug, Clone)]
+pub struct NetworkFsInfo {
+    pub is_network: bool,
+    pub reason: Option<String>,
+}
+
+impl NetworkFsInfo {
+    fn local() -> Self {
+        Self {
+            is_network: false,
+            reason: None,
+        }
+    }
+
+    fn network(reason: &str) -> Self {
+        Self {
+            is_network: true,
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// 探测 `path` 是否位于网络/远程文件系统上（NFS、SMB/CIFS、FUSE 等）
+pub fn probe(path: &Path) -> NetworkFsInfo {
+    #[cfg(target_os = "linux")]
+    {
+        linux_probe(path)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        heuristic_probe(path)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_probe(path: &Path) -> NetworkFsInfo {
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+    use std::os::unix::ffi::OsStrExt;
+
+    const NFS_SUPER_MAGIC: i64 = 0x6969;
+    const SMB2_MAGIC_NUMBER: i64 = 0xFE534D42u32 as i64;
+    const CIFS_MAGIC_NUMBER: i64 = 0xFF534D42u32 as i64;
+    const FUSE_SUPER_MAGIC: i64 = 0x65735546;
+
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return NetworkFsInfo::local();
+    };
+
+    let mut stat = MaybeUninit::<libc::statfs>::uninit();
+    let ret = unsafe { libc::statfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    if ret != 0 {
+        return NetworkFsInfo::local();
+    }
+
+    let f_type = unsafe { stat.assume_init() }.f_type as i64;
+
+    match f_type {
+        NFS_SUPER_MAGIC => NetworkFsInfo::network("NFS"),
+        SMB2_MAGIC_NUMBER => NetworkFsInfo::network("SMB2"),
+        CIFS_MAGIC_NUMBER => NetworkFsInfo::network("CIFS"),
+        FUSE_SUPER_MAGIC => NetworkFsInfo::network("FUSE"),
+        _ => NetworkFsInfo::local(),
+    }
+}
+
+/// 非 Linux 平台缺少可靠的文件系统类型 API，退化为路径特征的尽力猜测
+#[cfg(not(target_os = "linux"))]
+fn heuristic_probe(path: &Path) -> NetworkFsInfo {
+    let path_str = path.to_string_lossy();
+
+    // Windows UNC 路径：\\server\share\...
+    if path_str.starts_with(r"\\") || path_str.starts_with("//") {
+        return NetworkFsInfo::network("UNC 网络路径");
+    }
+
+    // macOS 下挂载的网络共享通常位于 /Volumes 下
+    #[cfg(target_os = "macos")]
+    if path_str.starts_with("/Volumes/") {
+        return NetworkFsInfo::network("挂载的网络共享");
+    }
+
+    NetworkFsInfo::local()
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::Local;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::backup::backup_file;
+use crate::config::{backup_dir_path, load_config};
+
+const DATA_FILE: &str = "cmd_notebook.json";
+
+/// 一条版本历史记录（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub hash: String,
+    pub timestamp: String,
+    pub message: String,
+}
+
+fn is_git_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn git_repo_exists(data_dir: &Path) -> bool {
+    data_dir.join(".git").exists()
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .map_err(|_| "执行 git 命令失败".to_string())?;
+
+    if !output.status.success() {
+        // 部分失败信息（如 commit 时的 "nothing to commit"）输出在 stdout 而非 stderr
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Err(format!("{}\n{}", stderr.trim(), stdout.trim()).trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// 若 `.git` 不存在则初始化仓库，并切换到配置的分支
+fn ensure_repo(data_dir: &Path, branch: &str) -> Result<(), String> {
+    fs::create_dir_all(data_dir).map_err(|_| "无法创建数据目录".to_string())?;
+
+    if git_repo_exists(data_dir) {
+        return Ok(());
+    }
+
+    run_git(data_dir, &["init"]).map_err(|e| format!("git init 失败：{}", e))?;
+    // 旧版本 git 没有 `init -b`，退而用 checkout -b 切换分支，失败也不影响功能
+    let _ = run_git(data_dir, &["checkout", "-b", branch]);
+
+    Ok(())
+}
+
+/// 若启用了版本历史，在一次成功保存后提交一个快照；`git` 不可用时静默跳过
+pub fn record_snapshot_if_enabled(app: &AppHandle) -> Result<(), String> {
+    let config = load_config(app)?;
+    if !config.git_history_enabled {
+        return Ok(());
+    }
+    if !is_git_available() {
+        return Ok(());
+    }
+
+    ensure_repo(&config.data_dir, &config.git_history_branch)?;
+    run_git(&config.data_dir, &["add", DATA_FILE]).map_err(|e| format!("git add 失败：{}", e))?;
+
+    let message = format!("snapshot {}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+    // 显式指定提交身份，避免在无法自动推断 user.name/user.email 的最小环境中提交失败
+    if let Err(err) = run_git(
+        &config.data_dir,
+        &[
+            "-c",
+            "user.name=cmd_notebook",
+            "-c",
+            "user.email=cmd_notebook@localhost",
+            "commit",
+            "-m",
+            &message,
+        ],
+    ) {
+        // 内容未变化导致的 commit 失败属正常情况，静默忽略；其他失败应让用户能察觉到
+        if !err.contains("nothing to commit") {
+            eprintln!("[git_history] 提交版本快照失败: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// 列出数据文件的最近提交历史
+pub fn list_history(app: &AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    let config = load_config(app)?;
+    if !git_repo_exists(&config.data_dir) {
+        return Ok(Vec::new());
+    }
+
+    let log = run_git(
+        &config.data_dir,
+        &[
+            "log",
+            "--pretty=format:%H%x1f%aI%x1f%s",
+            "-n",
+            "100",
+            "--",
+            DATA_FILE,
+        ],
+    )?;
+
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\u{1f}');
+            let hash = parts.next()?.to_string();
+            let timestamp = parts.next()?.to_string();
+            let message = parts.next().unwrap_or("").to_string();
+            Some(HistoryEntry {
+                hash,
+                timestamp,
+                message,
+            })
+        })
+        .collect())
+}
+
+/// 返回数据文件在某次提交处相对其上一版本的统一 diff
+pub fn diff_history(app: &AppHandle, hash: &str) -> Result<String, String> {
+    let config = load_config(app)?;
+
+    run_git(
+        &config.data_dir,
+        &["diff", &format!("{}~1", hash), hash, "--", DATA_FILE],
+    )
+    .or_else(|_| run_git(&config.data_dir, &["show", hash, "--", DATA_FILE]))
+}
+
+/// 取出某次提交处的数据文件内容，并备份当前数据以便可逆；
+/// 实际写入交由调用方完成，以便复用 `save_state` 的加锁与网络文件系统安全写入策略
+pub fn restore_history(app: &AppHandle, hash: &str) -> Result<String, String> {
+    let config = load_config(app)?;
+    let content = run_git(
+        &config.data_dir,
+        &["show", &format!("{}:{}", hash, DATA_FILE)],
+    )
+    .map_err(|e| format!("读取历史版本失败：{}", e))?;
+
+    let data_path = config.data_dir.join(DATA_FILE);
+    if data_path.exists() {
+        let backup_dir = backup_dir_path(app)?;
+        backup_file(&data_path, &backup_dir)?;
+    }
+
+    Ok(content)
+}
@@ -1,23 +1,38 @@
 mod backup;
 mod config;
+mod fsprobe;
+mod git_history;
+mod lock;
+mod watcher;
 
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Write};
 use std::path::PathBuf;
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 
-use backup::{backup_file, copy_data_to_new_dir, create_backup_if_changed};
+use backup::{
+    backup_file, copy_data_to_new_dir, create_backup_if_changed, list_backup_files,
+    restore_backup_file, BackupInfo,
+};
 use config::{
     check_target_dir, data_file_path, default_data_dir, is_dir_writable, load_config, save_config,
     AppConfig, DataDirInfo, SwitchDirAction, SwitchDirCheck,
 };
+use fsprobe::probe;
+use git_history::HistoryEntry;
+use lock::{ensure_locked, DirLockState};
+use watcher::{FileWatcherState, LastWrittenData};
 
 const DATA_FILE: &str = "cmd_notebook.json";
 
 #[tauri::command]
 async fn save_state(app: AppHandle, data: String) -> Result<(), String> {
-    let path = data_file_path(&app)?;
+    // 数据目录被另一个实例锁定时拒绝写入
+    ensure_locked(&app)?;
+
+    let config = load_config(&app)?;
+    let path = config.data_dir.join(DATA_FILE);
 
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|_| "无法创建数据目录".to_string())?;
@@ -26,10 +41,50 @@ async fn save_state(app: AppHandle, data: String) -> Result<(), String> {
     // 内容变化时创建备份
     create_backup_if_changed(&app, &data)?;
 
-    // 原子写入：先写临时文件，再 rename
+    // 网络文件系统上 rename 未必原子，切换到更安全的写入策略
+    let is_network = probe(&config.data_dir).is_network;
+    write_data_file(&path, &data, is_network)?;
+
+    // 记录本次写入的内容，避免监听器把这次保存误判为外部修改
+    *app.state::<LastWrittenData>().0.lock().unwrap() = Some(data);
+
+    // 可选的 Git 版本历史；git 不可用或提交失败都不应影响保存本身
+    if let Err(err) = git_history::record_snapshot_if_enabled(&app) {
+        eprintln!("[git_history] 记录版本历史失败: {}", err);
+    }
+
+    Ok(())
+}
+
+/// 原子写入：先写临时文件再 rename；网络文件系统上先 fsync 临时文件和目录，
+/// rename 失败时退化为直接覆盖写入
+fn write_data_file(path: &PathBuf, data: &str, is_network: bool) -> Result<(), String> {
     let tmp_path = path.with_extension("json.tmp");
-    fs::write(&tmp_path, &data).map_err(|_| "写入数据失败".to_string())?;
-    fs::rename(&tmp_path, &path).map_err(|_| "保存数据失败".to_string())?;
+
+    if !is_network {
+        fs::write(&tmp_path, data).map_err(|_| "写入数据失败".to_string())?;
+        fs::rename(&tmp_path, path).map_err(|_| "保存数据失败".to_string())?;
+        return Ok(());
+    }
+
+    {
+        let mut file = fs::File::create(&tmp_path).map_err(|_| "写入数据失败".to_string())?;
+        file.write_all(data.as_bytes())
+            .map_err(|_| "写入数据失败".to_string())?;
+        file.sync_all().map_err(|_| "写入数据失败".to_string())?;
+    }
+
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    if fs::rename(&tmp_path, path).is_err() {
+        // rename 在该网络文件系统上不可靠，退化为直接覆盖写入
+        fs::write(path, data).map_err(|_| "保存数据失败".to_string())?;
+        let _ = fs::remove_file(&tmp_path);
+    }
 
     Ok(())
 }
@@ -60,12 +115,15 @@ async fn get_data_dir_info(app: AppHandle) -> Result<DataDirInfo, String> {
     let config = load_config(&app)?;
     let default_dir = default_data_dir(&app)?;
     let data_path = config.data_dir.join(DATA_FILE);
+    let network_info = probe(&config.data_dir);
 
     Ok(DataDirInfo {
         path: config.data_dir.to_string_lossy().to_string(),
         is_default: config.data_dir == default_dir,
         data_file_exists: data_path.exists(),
         is_writable: is_dir_writable(&config.data_dir),
+        is_network: network_info.is_network,
+        network_reason: network_info.reason,
     })
 }
 
@@ -114,6 +172,11 @@ async fn switch_data_dir(
     save_config(&app, &config)?;
     eprintln!("[switch_data_dir] config saved successfully");
 
+    // 数据目录变了，释放旧锁并重新指向监听器
+    *app.state::<LastWrittenData>().0.lock().unwrap() = None;
+    watcher::restart_watching(&app)?;
+    lock::acquire_for_app(&app);
+
     Ok(())
 }
 
@@ -131,13 +194,66 @@ async fn reset_data_dir(app: AppHandle) -> Result<(), String> {
         }
     }
 
-    // 重置为默认目录
+    // 重置为默认目录，其余字段沿用旧配置，避免新增字段遗漏
     let config = AppConfig {
         data_dir: default_dir,
-        backup_count: load_config(&app)?.backup_count,
+        ..load_config(&app)?
     };
     save_config(&app, &config)?;
 
+    // 数据目录变了，释放旧锁并重新指向监听器
+    *app.state::<LastWrittenData>().0.lock().unwrap() = None;
+    watcher::restart_watching(&app)?;
+    lock::acquire_for_app(&app);
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    list_backup_files(&app)
+}
+
+#[tauri::command]
+async fn restore_backup(app: AppHandle, filename: String) -> Result<(), String> {
+    // 数据目录被另一个实例锁定时拒绝覆盖
+    ensure_locked(&app)?;
+
+    restore_backup_file(&app, &filename)?;
+
+    // 恢复后的内容也是本进程写入的，避免被监听器当作外部修改
+    let path = data_file_path(&app)?;
+    let content = fs::read_to_string(&path).ok();
+    *app.state::<LastWrittenData>().0.lock().unwrap() = content;
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_history(app: AppHandle) -> Result<Vec<HistoryEntry>, String> {
+    git_history::list_history(&app)
+}
+
+#[tauri::command]
+async fn diff_history(app: AppHandle, hash: String) -> Result<String, String> {
+    git_history::diff_history(&app, &hash)
+}
+
+#[tauri::command]
+async fn restore_history(app: AppHandle, hash: String) -> Result<(), String> {
+    // 数据目录被另一个实例锁定时拒绝覆盖
+    ensure_locked(&app)?;
+
+    let content = git_history::restore_history(&app, &hash)?;
+
+    let config = load_config(&app)?;
+    let path = config.data_dir.join(DATA_FILE);
+    let is_network = probe(&config.data_dir).is_network;
+    write_data_file(&path, &content, is_network)?;
+
+    // 记录本次写入的内容，避免监听器把这次恢复误判为外部修改
+    *app.state::<LastWrittenData>().0.lock().unwrap() = Some(content);
+
     Ok(())
 }
 
@@ -148,13 +264,29 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(LastWrittenData::default())
+        .manage(FileWatcherState::new())
+        .manage(DirLockState::default())
+        .setup(|app| {
+            let handle = app.handle().clone();
+            if let Err(err) = watcher::start_watching(&handle) {
+                eprintln!("[watcher] 启动文件监听失败: {}", err);
+            }
+            lock::acquire_for_app(&handle);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             save_state,
             load_state,
             get_data_dir_info,
             check_switch_dir,
             switch_data_dir,
-            reset_data_dir
+            reset_data_dir,
+            list_backups,
+            restore_backup,
+            list_history,
+            diff_history,
+            restore_history
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
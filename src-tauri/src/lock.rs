@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::load_config;
+
+const LOCK_FILE: &str = ".lock";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockInfo {
+    pid: u32,
+    timestamp: u64,
+}
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 探测锁文件中记录的 PID 是否还存活；返回 `None` 表示本平台无法可靠判断
+#[cfg(target_os = "linux")]
+fn is_pid_alive(pid: u32) -> Option<bool> {
+    Some(Path::new(&format!("/proc/{}", pid)).exists())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_pid_alive(_pid: u32) -> Option<bool> {
+    // 非 Linux 平台暂无可靠的存活探测，交由时间戳超时来判断
+    None
+}
+
+fn read_lock(path: &Path) -> Option<LockInfo> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_lock(path: &Path) -> Result<(), String> {
+    let info = LockInfo {
+        pid: process::id(),
+        timestamp: now_unix_secs(),
+    };
+    let content = serde_json::to_string(&info).map_err(|_| "锁文件序列化失败".to_string())?;
+    fs::write(path, content).map_err(|_| "无法创建锁文件".to_string())
+}
+
+/// 锁有效需同时满足：时间戳仍在有效期内，且（若能判断）PID 确实存活。
+/// 持有锁的实例会通过心跳线程定期刷新时间戳，因此这里的超时检查不会误伤正常运行的实例；
+/// 但仍保留超时作为后备，避免进程崩溃后 PID 被无关进程复用导致锁永久无法回收。
+fn is_lock_live(info: &LockInfo, stale_after_secs: u64) -> bool {
+    let fresh = now_unix_secs().saturating_sub(info.timestamp) <= stale_after_secs;
+    match is_pid_alive(info.pid) {
+        Some(alive) => alive && fresh,
+        None => fresh,
+    }
+}
+
+/// 心跳间隔：陈旧超时的三分之一，确保在超时窗口内至少刷新两次
+fn heartbeat_interval(stale_after_secs: u64) -> Duration {
+    Duration::from_secs((stale_after_secs / 3).max(1))
+}
+
+/// 后台线程定期重写锁文件的时间戳，使其在本实例持有期间不会被判定为陈旧
+fn spawn_heartbeat(lock_path: PathBuf, stale_after_secs: u64, stop: Arc<AtomicBool>) {
+    let interval = heartbeat_interval(stale_after_secs);
+    thread::spawn(move || {
+        while !stop.load(Ordering::SeqCst) {
+            thread::sleep(interval);
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            let _ = write_lock(&lock_path);
+        }
+    });
+}
+
+/// 数据目录的独占锁，持有期间 `.lock` 文件存在并由心跳线程保活，Drop 时自动清理
+pub struct DirLockGuard {
+    path: PathBuf,
+    stop_heartbeat: Arc<AtomicBool>,
+}
+
+impl DirLockGuard {
+    /// 非阻塞地获取目录锁；若锁被存活进程持有则直接失败，陈旧锁会被回收
+    pub fn acquire(dir: &Path, stale_after_secs: u64) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|_| "无法创建数据目录".to_string())?;
+        let lock_path = dir.join(LOCK_FILE);
+
+        if let Some(existing) = read_lock(&lock_path) {
+            if is_lock_live(&existing, stale_after_secs) {
+                return Err(format!(
+                    "数据目录已被另一个实例占用（PID {}）",
+                    existing.pid
+                ));
+            }
+        }
+
+        write_lock(&lock_path)?;
+
+        let stop_heartbeat = Arc::new(AtomicBool::new(false));
+        spawn_heartbeat(lock_path.clone(), stale_after_secs, stop_heartbeat.clone());
+
+        Ok(Self {
+            path: lock_path,
+            stop_heartbeat,
+        })
+    }
+}
+
+impl Drop for DirLockGuard {
+    fn drop(&mut self) {
+        self.stop_heartbeat.store(true, Ordering::SeqCst);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// 当前目录锁的状态，持有锁或说明为何无法持有
+pub enum DirLockStatus {
+    Held(DirLockGuard),
+    Unavailable(String),
+}
+
+/// 目录锁在 Tauri 托管状态中的容器
+pub struct DirLockState(pub Mutex<DirLockStatus>);
+
+impl Default for DirLockState {
+    fn default() -> Self {
+        Self(Mutex::new(DirLockStatus::Unavailable(
+            "尚未初始化".to_string(),
+        )))
+    }
+}
+
+/// 为当前 `data_dir` 获取锁并更新托管状态；在启动和切换目录后调用
+pub fn acquire_for_app(app: &AppHandle) {
+    let state = app.state::<DirLockState>();
+    let mut status = state.0.lock().unwrap();
+    // 先释放旧锁再获取新锁，否则新旧目录相同时会被本进程自己的锁挡住
+    *status = DirLockStatus::Unavailable("尚未初始化".to_string());
+    *status = match try_acquire(app) {
+        Ok(guard) => DirLockStatus::Held(guard),
+        Err(reason) => DirLockStatus::Unavailable(reason),
+    };
+}
+
+fn try_acquire(app: &AppHandle) -> Result<DirLockGuard, String> {
+    let config = load_config(app)?;
+    DirLockGuard::acquire(&config.data_dir, config.lock_stale_secs)
+}
+
+/// 若目录锁不可用，返回应提示给前端的错误信息
+pub fn ensure_locked(app: &AppHandle) -> Result<(), String> {
+    match &*app.state::<DirLockState>().0.lock().unwrap() {
+        DirLockStatus::Held(_) => Ok(()),
+        DirLockStatus::Unavailable(reason) => Err(reason.clone()),
+    }
+}
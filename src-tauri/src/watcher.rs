@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{
+    config_file_path, data_file_path, default_data_dir, is_dir_writable, load_config, DataDirInfo,
+};
+use crate::fsprobe::probe;
+
+const DATA_FILE: &str = "cmd_notebook.json";
+
+/// 合并同一轮突发事件（例如一次保存触发的多个文件系统事件）的去抖窗口
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 记录本进程最近一次写入数据文件的内容，用于区分“自己保存”和“外部修改”
+#[derive(Default)]
+pub struct LastWrittenData(pub Arc<Mutex<Option<String>>>);
+
+/// 当前生效的文件监听器，切换数据目录时需要重建
+pub struct FileWatcherState(Mutex<Option<RecommendedWatcher>>);
+
+impl FileWatcherState {
+    pub fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+impl Default for FileWatcherState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `data-file-changed` 事件负载
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataFileChangedPayload {
+    content: Option<String>,
+}
+
+/// 启动（或在切换目录后重启）对数据文件与配置文件的监听
+pub fn start_watching(app: &AppHandle) -> Result<(), String> {
+    let config = load_config(app)?;
+    let data_path = data_file_path(app)?;
+    let config_path = config_file_path(app)?;
+
+    let data_dir = config.data_dir.clone();
+    let config_dir = config_path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .ok_or_else(|| "无法确定配置目录".to_string())?;
+
+    let last_written = app.state::<LastWrittenData>().0.clone();
+
+    let watcher = spawn_debounced_watcher(
+        app.clone(),
+        data_path,
+        config_path,
+        vec![data_dir, config_dir],
+        last_written,
+    )?;
+
+    let state = app.state::<FileWatcherState>();
+    *state.0.lock().map_err(|_| "监听器状态已损坏".to_string())? = Some(watcher);
+
+    Ok(())
+}
+
+/// 切换数据目录后重新指向监听器
+pub fn restart_watching(app: &AppHandle) -> Result<(), String> {
+    start_watching(app)
+}
+
+fn spawn_debounced_watcher(
+    app: AppHandle,
+    data_path: PathBuf,
+    config_path: PathBuf,
+    watch_dirs: Vec<PathBuf>,
+    last_written: Arc<Mutex<Option<String>>>,
+) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = channel::<notify::Result<Event>>();
+
+    let mut watcher = notify::recommended_watcher(move |res| {
+        let _ = tx.send(res);
+    })
+    .map_err(|_| "无法创建文件监听器".to_string())?;
+
+    for dir in &watch_dirs {
+        if dir.exists() {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|_| "无法监听目录".to_string())?;
+        }
+    }
+
+    thread::spawn(move || debounce_loop(app, rx, data_path, config_path, last_written));
+
+    Ok(watcher)
+}
+
+/// 合并去抖窗口内的事件，窗口结束后按文件各处理一次
+fn debounce_loop(
+    app: AppHandle,
+    rx: Receiver<notify::Result<Event>>,
+    data_path: PathBuf,
+    config_path: PathBuf,
+    last_written: Arc<Mutex<Option<String>>>,
+) {
+    let mut pending: HashSet<PathBuf> = HashSet::new();
+
+    loop {
+        let timeout = if pending.is_empty() {
+            Duration::from_secs(3600)
+        } else {
+            DEBOUNCE
+        };
+
+        match rx.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                if matches!(
+                    event.kind,
+                    EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                ) {
+                    pending.extend(event.paths);
+                }
+            }
+            Ok(Err(_)) => {}
+            Err(RecvTimeoutError::Timeout) => {
+                for path in pending.drain() {
+                    if path == data_path {
+                        handle_data_file_changed(&app, &data_path, &last_written);
+                    } else if path == config_path {
+                        handle_config_changed(&app);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+fn handle_data_file_changed(
+    app: &AppHandle,
+    data_path: &PathBuf,
+    last_written: &Arc<Mutex<Option<String>>>,
+) {
+    let content = fs::read_to_string(data_path).ok();
+
+    let is_own_write = {
+        let guard = last_written.lock().unwrap_or_else(|e| e.into_inner());
+        *guard == content
+    };
+    if is_own_write {
+        return;
+    }
+
+    if let Ok(mut guard) = last_written.lock() {
+        *guard = content.clone();
+    }
+
+    let _ = app.emit("data-file-changed", DataFileChangedPayload { content });
+}
+
+fn handle_config_changed(app: &AppHandle) {
+    let Ok(config) = load_config(app) else {
+        return;
+    };
+    let Ok(default_dir) = default_data_dir(app) else {
+        return;
+    };
+    let data_path = config.data_dir.join(DATA_FILE);
+    let network_info = probe(&config.data_dir);
+
+    let info = DataDirInfo {
+        path: config.data_dir.to_string_lossy().to_string(),
+        is_default: config.data_dir == default_dir,
+        data_file_exists: data_path.exists(),
+        is_writable: is_dir_writable(&config.data_dir),
+        is_network: network_info.is_network,
+        network_reason: network_info.reason,
+    };
+
+    let _ = app.emit("config-changed", info);
+}